@@ -0,0 +1,225 @@
+use anyhow::Context;
+use std::fs;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// The subset of a path's resolved `.gitattributes` state this crate cares
+/// about when cleaning a blob on its way into the object store.
+#[derive(Debug, Default)]
+struct Attributes {
+    text: Option<bool>,
+    eol: Option<String>,
+    filter: Option<String>,
+}
+
+/// The clean transformation to apply to a file's bytes before hashing them,
+/// as resolved from `.gitattributes` (and, for a named filter, `.git/config`).
+pub(crate) enum CleanFilter {
+    /// No matching attribute - use the file's bytes unchanged.
+    None,
+    /// `text` or `eol=lf`: normalize CRLF line endings to LF.
+    NormalizeToLf,
+    /// `filter=<name>` with a configured `filter.<name>.clean` command.
+    External(String),
+}
+
+impl CleanFilter {
+    pub(crate) fn apply(&self, input: &[u8]) -> anyhow::Result<Vec<u8>> {
+        match self {
+            CleanFilter::None => Ok(input.to_vec()),
+            CleanFilter::NormalizeToLf => Ok(normalize_crlf_to_lf(input)),
+            CleanFilter::External(command) => run_external_clean(command, input),
+        }
+    }
+}
+
+/// Resolve which clean filter applies to `path`, by walking `.gitattributes`
+/// files from the repository root down to the file's directory (later files
+/// override earlier ones, as does a later matching line within one file).
+pub(crate) fn clean_filter_for(path: &Path) -> anyhow::Result<CleanFilter> {
+    let attrs = resolve_attributes(path)?;
+
+    if let Some(name) = &attrs.filter {
+        if let Some(command) = external_clean_command(name)? {
+            return Ok(CleanFilter::External(command));
+        }
+    }
+
+    if attrs.eol.as_deref() == Some("lf") || attrs.text == Some(true) {
+        return Ok(CleanFilter::NormalizeToLf);
+    }
+
+    Ok(CleanFilter::None)
+}
+
+fn resolve_attributes(path: &Path) -> anyhow::Result<Attributes> {
+    let mut attrs = Attributes::default();
+
+    for dir in ancestor_dirs(path) {
+        let gitattributes_path = dir.join(".gitattributes");
+        let Ok(content) = fs::read_to_string(&gitattributes_path) else {
+            continue;
+        };
+
+        let relative = path.strip_prefix(&dir).unwrap_or(path).to_string_lossy().into_owned();
+        let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut parts = line.split_whitespace();
+            let Some(pattern) = parts.next() else {
+                continue;
+            };
+
+            let candidate = if pattern.contains('/') { relative.as_str() } else { file_name };
+            if !glob_match(pattern.trim_start_matches('/'), candidate) {
+                continue;
+            }
+
+            for spec in parts {
+                apply_attr_spec(spec, &mut attrs);
+            }
+        }
+    }
+
+    Ok(attrs)
+}
+
+/// `["." , "a", "a/b"]` for a file at `a/b/c.txt` - the directories whose
+/// `.gitattributes` can affect it, root first so later ones take priority.
+fn ancestor_dirs(path: &Path) -> Vec<PathBuf> {
+    let mut dirs = vec![PathBuf::from(".")];
+    let mut acc = PathBuf::new();
+
+    if let Some(parent) = path.parent() {
+        for component in parent.components() {
+            acc.push(component.as_os_str());
+            dirs.push(acc.clone());
+        }
+    }
+
+    dirs
+}
+
+fn apply_attr_spec(spec: &str, attrs: &mut Attributes) {
+    if let Some(name) = spec.strip_prefix('-') {
+        if name == "text" {
+            attrs.text = Some(false);
+        }
+        return;
+    }
+
+    if let Some((name, value)) = spec.split_once('=') {
+        match name {
+            "eol" => attrs.eol = Some(value.to_string()),
+            "filter" => attrs.filter = Some(value.to_string()),
+            _ => {}
+        }
+        return;
+    }
+
+    if spec == "text" {
+        attrs.text = Some(true);
+    }
+}
+
+/// Minimal shell-style glob: `*` matches any run of characters, `?` matches
+/// exactly one, everything else is literal.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    fn go(pattern: &[u8], text: &[u8]) -> bool {
+        match (pattern.first(), text.first()) {
+            (None, None) => true,
+            (Some(b'*'), _) => go(&pattern[1..], text) || (!text.is_empty() && go(pattern, &text[1..])),
+            (Some(b'?'), Some(_)) => go(&pattern[1..], &text[1..]),
+            (Some(p), Some(t)) if p == t => go(&pattern[1..], &text[1..]),
+            _ => false,
+        }
+    }
+    go(pattern.as_bytes(), text.as_bytes())
+}
+
+fn normalize_crlf_to_lf(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len());
+    let mut i = 0;
+    while i < data.len() {
+        if data[i] == b'\r' && data.get(i + 1) == Some(&b'\n') {
+            out.push(b'\n');
+            i += 2;
+        } else {
+            out.push(data[i]);
+            i += 1;
+        }
+    }
+    out
+}
+
+/// Look up `filter.<name>.clean` in `.git/config`.
+fn external_clean_command(name: &str) -> anyhow::Result<Option<String>> {
+    let Ok(config) = fs::read_to_string(".git/config") else {
+        return Ok(None);
+    };
+
+    let section = format!("[filter \"{name}\"]");
+    let mut in_section = false;
+
+    for line in config.lines() {
+        let line = line.trim();
+        if line.eq_ignore_ascii_case(&section) {
+            in_section = true;
+            continue;
+        }
+        if line.starts_with('[') {
+            in_section = false;
+            continue;
+        }
+        if in_section {
+            if let Some((key, value)) = line.split_once('=') {
+                if key.trim() == "clean" {
+                    return Ok(Some(value.trim().to_string()));
+                }
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+/// Run a configured external clean command, feeding it `input` on stdin and
+/// taking its stdout as the cleaned content.
+fn run_external_clean(command: &str, input: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("spawn clean filter '{command}'"))?;
+
+    // Write stdin from a separate thread: if the filter writes more to
+    // stdout than fits in the OS pipe buffer before it's finished reading
+    // stdin, writing stdin to completion here first would deadlock against
+    // the filter blocked writing a now-full stdout pipe.
+    let mut stdin = child.stdin.take().expect("stdin was piped");
+    let input = input.to_vec();
+    let writer = std::thread::spawn(move || stdin.write_all(&input));
+
+    let output = child
+        .wait_with_output()
+        .with_context(|| format!("run clean filter '{command}'"))?;
+    writer
+        .join()
+        .expect("stdin-writer thread panicked")
+        .with_context(|| format!("write input to clean filter '{command}'"))?;
+    anyhow::ensure!(
+        output.status.success(),
+        "clean filter '{command}' exited with {}",
+        output.status
+    );
+
+    Ok(output.stdout)
+}
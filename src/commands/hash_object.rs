@@ -1,33 +1,38 @@
+use crate::hash::HashKind;
 use crate::objects::Object;
 use anyhow::Context;
 use std::path::Path;
 
-/// Execute the hash-object command to compute the SHA-1 hash of a file.
-/// 
+/// Execute the hash-object command to compute the hash of a file.
+///
 /// If `write` is true, the blob object is written to `.git/objects/` directory.
 /// If `write` is false, only the hash is computed and printed (without writing to disk).
-/// 
+///
 /// The function uses the `Object` abstraction to create a blob from the file,
 /// then either writes it to the objects directory or computes the hash by writing to a sink.
+/// The hash algorithm itself (SHA-1 or SHA-256) comes from the repository's
+/// `.git/config`.
 pub fn execute(write: bool, file: &Path) -> anyhow::Result<()> {
     // Create a blob object from the file using the Object abstraction
     let object = Object::blob_from_file(file).context("open blob input file")?;
-    
+
+    let hash_kind = HashKind::from_repo_config().context("determine repository hash algorithm")?;
+
     // Compute the hash, optionally writing to .git/objects/
     let hash = if write {
         // Write the blob object to .git/objects/ and return its hash
         object
-            .write_to_objects()
+            .write_to_objects(hash_kind)
             .context("stream file into blob object file")?
     } else {
         // Compute hash by writing to a sink (no disk I/O)
         object
-            .write(std::io::sink())
+            .write(std::io::sink(), hash_kind)
             .context("stream file into blob object")?
     };
 
     // Print the hash as a hexadecimal string
-    println!("{}", hex::encode(hash));
+    println!("{hash}");
 
     Ok(())
 }
@@ -0,0 +1,90 @@
+use crate::commit::Commit;
+use crate::objects::{Kind, Object};
+use anyhow::Context;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashSet};
+
+/// Execute the log command: walk the parent graph from `commit_ish`, printing
+/// commits newest-first.
+pub fn execute(commit_ish: &str) -> anyhow::Result<()> {
+    let start = crate::refs::resolve_commit_ish(commit_ish)?;
+
+    let mut heap = BinaryHeap::new();
+    let mut visited = HashSet::new();
+    let mut seq = 0u64;
+
+    push(&mut heap, &mut visited, &mut seq, start)?;
+
+    while let Some(QueueEntry { hash, commit, .. }) = heap.pop() {
+        println!("commit {hash}");
+        println!("Author:    {}", commit.author);
+        println!("Committer: {}", commit.committer);
+        println!();
+        println!("    {}", commit.summary());
+        println!();
+
+        for parent in &commit.parents {
+            push(&mut heap, &mut visited, &mut seq, parent.clone())?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Load and enqueue `hash` if it hasn't been visited yet.
+fn push(
+    heap: &mut BinaryHeap<QueueEntry>,
+    visited: &mut HashSet<String>,
+    seq: &mut u64,
+    hash: String,
+) -> anyhow::Result<()> {
+    if !visited.insert(hash.clone()) {
+        return Ok(());
+    }
+
+    let object = Object::read(&hash).with_context(|| format!("read commit {hash}"))?;
+    anyhow::ensure!(
+        object.kind == Kind::Commit,
+        "{hash} is a {}, not a commit",
+        object.kind
+    );
+    let commit = Commit::parse(object.reader).with_context(|| format!("parse commit {hash}"))?;
+
+    *seq += 1;
+    heap.push(QueueEntry {
+        time: commit.committer_time,
+        seq: *seq,
+        hash,
+        commit,
+    });
+    Ok(())
+}
+
+/// A commit waiting to be printed, ordered so the heap pops the newest
+/// (highest committer timestamp) first, breaking ties by insertion order.
+struct QueueEntry {
+    time: i64,
+    seq: u64,
+    hash: String,
+    commit: Commit,
+}
+
+impl PartialEq for QueueEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.time == other.time && self.seq == other.seq
+    }
+}
+impl Eq for QueueEntry {}
+
+impl PartialOrd for QueueEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for QueueEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Lower `seq` (inserted earlier) compares greater, so it pops first.
+        self.time.cmp(&other.time).then(other.seq.cmp(&self.seq))
+    }
+}
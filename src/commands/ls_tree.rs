@@ -0,0 +1,31 @@
+use crate::objects::{Kind, Object};
+use crate::tree;
+use anyhow::Context;
+
+/// Execute the ls-tree command: list the entries of a tree object.
+pub fn execute(name_only: bool, tree_ish: &str) -> anyhow::Result<()> {
+    let object = Object::read(tree_ish).context("parse tree object")?;
+    anyhow::ensure!(
+        object.kind == Kind::Tree,
+        "{tree_ish} is a {}, not a tree",
+        object.kind
+    );
+
+    let entries = tree::parse(object.reader).context("parse tree entries")?;
+
+    for entry in entries {
+        if name_only {
+            println!("{}", entry.name);
+        } else {
+            println!(
+                "{:0>6} {} {}\t{}",
+                entry.mode,
+                entry.kind(),
+                hex::encode(entry.hash),
+                entry.name
+            );
+        }
+    }
+
+    Ok(())
+}
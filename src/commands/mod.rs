@@ -0,0 +1,5 @@
+pub mod cat_file;
+pub mod hash_object;
+pub mod log;
+pub mod ls_tree;
+pub mod verify;
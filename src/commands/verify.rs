@@ -0,0 +1,88 @@
+use crate::hash::{HashKind, Hasher};
+use crate::objects::Object;
+use anyhow::Context;
+use std::io::prelude::*;
+
+/// Execute the verify (fsck) command: re-derive each object's id from its
+/// stored content and compare it to the name it's stored under.
+pub fn execute(hash: Option<String>, all: bool) -> anyhow::Result<()> {
+    let hash_kind = HashKind::from_repo_config().context("determine repository hash algorithm")?;
+
+    if all {
+        let mut count = 0;
+        for hash in loose_object_hashes()? {
+            verify_one(&hash, hash_kind)?;
+            count += 1;
+        }
+        println!("{count} objects verified");
+    } else {
+        let hash = hash.context("verify requires a hash, or --all to check every object")?;
+        verify_one(&hash, hash_kind)?;
+        println!("{hash} ok");
+    }
+
+    Ok(())
+}
+
+/// Re-hash a single loose object and check it against its own name.
+fn verify_one(hash: &str, hash_kind: HashKind) -> anyhow::Result<()> {
+    let object = Object::read(hash).with_context(|| format!("read object {hash}"))?;
+
+    let mut hasher = Hasher::new(hash_kind);
+    hasher.update(format!("{} {}\0", object.kind, object.expected_size).as_bytes());
+
+    let mut reader = object.reader;
+    let mut buf = [0u8; 8192];
+    let mut total = 0u64;
+    loop {
+        // `reader` is capped one byte past `expected_size` (see `Object::read`),
+        // so a well-formed object stops on its own, while a padded one yields
+        // exactly one extra byte here - enough for the size check below to
+        // catch it without reading an unbounded amount of trailing garbage.
+        let n = reader.read(&mut buf).context("read object content")?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+        total += n as u64;
+    }
+    anyhow::ensure!(
+        total == object.expected_size,
+        "object {hash} declares size {} but decompressed to {total} bytes",
+        object.expected_size
+    );
+
+    let actual = hasher.finalize().to_hex();
+    anyhow::ensure!(
+        actual == hash,
+        "object {hash} is corrupt: desired {hash}, actual {actual}"
+    );
+
+    Ok(())
+}
+
+/// List every hash under `.git/objects/`, skipping the `pack` and `info`
+/// subdirectories which don't hold loose objects.
+fn loose_object_hashes() -> anyhow::Result<Vec<String>> {
+    let mut hashes = Vec::new();
+
+    for dir_entry in std::fs::read_dir(".git/objects").context("read .git/objects")? {
+        let dir_entry = dir_entry?;
+        let dir_name = dir_entry.file_name();
+        let Some(dir_name) = dir_name.to_str() else {
+            continue;
+        };
+        if dir_name.len() != 2 || !dir_name.bytes().all(|b| b.is_ascii_hexdigit()) {
+            continue;
+        }
+
+        for file_entry in std::fs::read_dir(dir_entry.path())? {
+            let file_entry = file_entry?;
+            if let Some(file_name) = file_entry.file_name().to_str() {
+                hashes.push(format!("{dir_name}{file_name}"));
+            }
+        }
+    }
+
+    Ok(hashes)
+}
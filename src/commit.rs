@@ -0,0 +1,87 @@
+use anyhow::Context;
+use std::io::prelude::*;
+
+/// A parsed git commit object.
+///
+/// A commit's decompressed body is a set of header lines followed by a blank
+/// line and the free-form message:
+///
+/// ```text
+/// tree <40-hex>
+/// parent <40-hex>        (zero or more)
+/// author <name> <email> <unix-ts> <tz>
+/// committer <name> <email> <unix-ts> <tz>
+///
+/// <message>
+/// ```
+#[derive(Debug)]
+pub(crate) struct Commit {
+    pub(crate) tree: String,
+    pub(crate) parents: Vec<String>,
+    pub(crate) author: String,
+    pub(crate) committer: String,
+    pub(crate) committer_time: i64,
+    pub(crate) message: String,
+}
+
+impl Commit {
+    /// Parse a commit from the decompressed content of a `Kind::Commit` object.
+    pub(crate) fn parse(reader: impl Read) -> anyhow::Result<Self> {
+        let mut content = String::new();
+        std::io::BufReader::new(reader)
+            .read_to_string(&mut content)
+            .context("read commit content")?;
+
+        let (header, message) = content
+            .split_once("\n\n")
+            .context("commit has no header/message separator")?;
+
+        let mut tree = None;
+        let mut parents = Vec::new();
+        let mut author = None;
+        let mut committer = None;
+        let mut committer_time = None;
+
+        for line in header.lines() {
+            let (key, value) = line
+                .split_once(' ')
+                .with_context(|| format!("malformed commit header line: '{line}'"))?;
+            match key {
+                "tree" => tree = Some(value.to_string()),
+                "parent" => parents.push(value.to_string()),
+                "author" => author = Some(value.to_string()),
+                "committer" => {
+                    committer = Some(value.to_string());
+                    committer_time = Some(parse_committer_time(value)?);
+                }
+                _ => {
+                    // Unknown header lines (e.g. gpgsig) are ignored.
+                }
+            }
+        }
+
+        Ok(Commit {
+            tree: tree.context("commit missing 'tree' header")?,
+            parents,
+            author: author.context("commit missing 'author' header")?,
+            committer: committer.context("commit missing 'committer' header")?,
+            committer_time: committer_time.context("commit missing 'committer' header")?,
+            message: message.to_string(),
+        })
+    }
+
+    /// The first line of the commit message.
+    pub(crate) fn summary(&self) -> &str {
+        self.message.lines().next().unwrap_or_default()
+    }
+}
+
+/// Pull the integer unix timestamp out of a `committer <name> <email> <ts> <tz>` line.
+fn parse_committer_time(committer: &str) -> anyhow::Result<i64> {
+    let ts = committer
+        .rsplit(' ')
+        .nth(1)
+        .with_context(|| format!("committer line missing timestamp: '{committer}'"))?;
+    ts.parse::<i64>()
+        .with_context(|| format!("committer line has invalid timestamp: '{ts}'"))
+}
@@ -0,0 +1,97 @@
+use sha1::Digest as _;
+use sha1::Sha1;
+use sha2::Sha256;
+use std::fmt;
+use std::fs;
+
+/// Which hash algorithm a repository's object store uses.
+///
+/// Classic git repositories use SHA-1; newer ones opt into the SHA-256
+/// object format via `extensions.objectformat = sha256` in `.git/config`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum HashKind {
+    Sha1,
+    Sha256,
+}
+
+impl HashKind {
+    /// Number of raw bytes in a digest of this kind.
+    pub(crate) fn len(self) -> usize {
+        match self {
+            HashKind::Sha1 => 20,
+            HashKind::Sha256 => 32,
+        }
+    }
+
+    /// Read `.git/config` and determine which hash algorithm the repository
+    /// was initialised with. Defaults to SHA-1 when unset, matching git.
+    pub(crate) fn from_repo_config() -> anyhow::Result<Self> {
+        let Ok(config) = fs::read_to_string(".git/config") else {
+            return Ok(HashKind::Sha1);
+        };
+
+        let uses_sha256 = config.lines().any(|line| {
+            let line = line.trim();
+            let Some((key, value)) = line.split_once('=') else {
+                return false;
+            };
+            key.trim().eq_ignore_ascii_case("objectformat") && value.trim().eq_ignore_ascii_case("sha256")
+        });
+
+        Ok(if uses_sha256 { HashKind::Sha256 } else { HashKind::Sha1 })
+    }
+}
+
+/// An object id: the variable-length digest naming a git object. Storing the
+/// raw bytes rather than a fixed-size array lets this hold either a 20-byte
+/// SHA-1 or a 32-byte SHA-256 digest and render hex of the correct width.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ObjectId {
+    bytes: Vec<u8>,
+}
+
+impl ObjectId {
+    pub(crate) fn to_hex(&self) -> String {
+        hex::encode(&self.bytes)
+    }
+}
+
+impl fmt::Display for ObjectId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_hex())
+    }
+}
+
+/// A hasher for whichever algorithm a repository is configured to use.
+///
+/// `Sha1` and `Sha256` are distinct types, so this dispatches at runtime
+/// rather than being generic - the algorithm is only known once `.git/config`
+/// has been read.
+pub(crate) enum Hasher {
+    Sha1(Sha1),
+    Sha256(Sha256),
+}
+
+impl Hasher {
+    pub(crate) fn new(kind: HashKind) -> Self {
+        match kind {
+            HashKind::Sha1 => Hasher::Sha1(Sha1::new()),
+            HashKind::Sha256 => Hasher::Sha256(Sha256::new()),
+        }
+    }
+
+    pub(crate) fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Sha1(h) => h.update(data),
+            Hasher::Sha256(h) => h.update(data),
+        }
+    }
+
+    pub(crate) fn finalize(self) -> ObjectId {
+        let bytes = match self {
+            Hasher::Sha1(h) => h.finalize().to_vec(),
+            Hasher::Sha256(h) => h.finalize().to_vec(),
+        };
+        ObjectId { bytes }
+    }
+}
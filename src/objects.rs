@@ -1,9 +1,8 @@
+use crate::hash::{HashKind, Hasher, ObjectId};
 use anyhow::Context;
 use flate2::Compression;
 use flate2::read::ZlibDecoder;
 use flate2::write::ZlibEncoder;
-use sha1::Digest;
-use sha1::Sha1;
 use std::ffi::CStr;
 use std::fmt;
 use std::fs;
@@ -17,7 +16,7 @@ use std::path::Path;
 /// - Blob: file content
 /// - Tree: directory structure
 /// - Commit: commit metadata
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum Kind {
     Blob,
     Tree,
@@ -51,21 +50,25 @@ pub(crate) struct Object<R> {
 impl Object<()> {
     /// Create a blob object from a file on disk.
     ///
-    /// This reads the file's metadata to get its size, then opens the file for reading.
-    /// The returned Object can be used to compute the hash or write to .git/objects/.
-    ///
-    /// TODO: technically there's a race here if the file changes between stat and write
+    /// Reads the whole file, applies whatever `.gitattributes` clean filter
+    /// matches its path (see [`crate::attributes`]), and sizes the blob from
+    /// the filtered content rather than the on-disk `stat` size - filters can
+    /// change the length, and this also sidesteps the stat/write race a pure
+    /// streaming read would have.
     pub(crate) fn blob_from_file(file: impl AsRef<Path>) -> anyhow::Result<Object<impl Read>> {
         let file = file.as_ref();
-        // Get file metadata to determine the blob size
-        let stat = std::fs::metadata(file).with_context(|| format!("stat {}", file.display()))?;
-        // TODO: technically there's a race here if the file changes between stat and write
-        // Open the file for reading
-        let file = std::fs::File::open(file).with_context(|| format!("open {}", file.display()))?;
+        let raw = fs::read(file).with_context(|| format!("read {}", file.display()))?;
+
+        let filter = crate::attributes::clean_filter_for(file)
+            .with_context(|| format!("resolve clean filter for {}", file.display()))?;
+        let content = filter
+            .apply(&raw)
+            .with_context(|| format!("apply clean filter to {}", file.display()))?;
+
         Ok(Object {
             kind: Kind::Blob,
-            expected_size: stat.len(),
-            reader: file,
+            expected_size: content.len() as u64,
+            reader: std::io::Cursor::new(content),
         })
     }
 
@@ -83,12 +86,21 @@ impl Object<()> {
     /// 3. Parses the header to extract kind and size
     /// 4. Returns an Object with a reader limited to the expected size
     ///
+    /// If no loose object exists under that hash, falls back to looking it up in
+    /// `.git/objects/pack/*.pack` (see [`crate::pack`]) and reconstructing it from
+    /// there, so callers don't need to know whether an object is loose or packed.
+    ///
     /// TODO: support shortest-unique object hashes
-    pub(crate) fn read(hash: &str) -> anyhow::Result<Object<impl BufRead>> {
+    pub(crate) fn read(hash: &str) -> anyhow::Result<Object<Box<dyn BufRead>>> {
         // TODO: support shortest-unique object hashes
         // Open the object file (first 2 chars are directory, rest is filename)
-        let f = std::fs::File::open(format!(".git/objects/{}/{}", &hash[..2], &hash[2..]))
-            .context("open in .git/objects")?;
+        let loose = std::fs::File::open(format!(".git/objects/{}/{}", &hash[..2], &hash[2..]));
+
+        let f = match loose {
+            Ok(f) => f,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Self::read_from_pack(hash),
+            Err(e) => return Err(e).context("open in .git/objects"),
+        };
 
         // Create a Zlib decoder to decompress the object file
         let z = ZlibDecoder::new(f);
@@ -128,15 +140,40 @@ impl Object<()> {
             .parse::<u64>()
             .context(".git/objects file header has invalid size: {size}")?;
 
-        // Limit the reader to only read 'size' bytes
-        // NOTE: this won't error if the decompressed file is too long, but will at least not
-        // spam stdout and be vulnerable to a zipbomb.
-        let z = z.take(size);
+        // Limit the reader to 'size' + 1 bytes: enough headroom that a caller
+        // comparing bytes-read against `expected_size` (see `verify`) can tell
+        // a padded object from a well-formed one, but still capped so this
+        // isn't vulnerable to a zipbomb.
+        let z = z.take(size + 1);
 
         Ok(Object {
             kind,
             expected_size: size,
-            reader: z,
+            reader: Box::new(z),
+        })
+    }
+
+    /// Resolve `hash` against the packfiles in `.git/objects/pack/`.
+    ///
+    /// Packs use git's original SHA-1 idx format, so this only resolves
+    /// 40-hex (SHA-1) object ids; SHA-256 repositories have no legacy packs
+    /// to fall back to.
+    fn read_from_pack(hash: &str) -> anyhow::Result<Object<Box<dyn BufRead>>> {
+        let raw = hex::decode(hash).with_context(|| format!("'{hash}' is not a valid hash"))?;
+        anyhow::ensure!(
+            raw.len() == HashKind::Sha1.len(),
+            "object {hash} not found (pack lookup only supports SHA-1 object ids)"
+        );
+        let raw: [u8; 20] = raw.try_into().unwrap();
+
+        let (kind, content) = crate::pack::resolve(&raw)
+            .context("search .git/objects/pack")?
+            .with_context(|| format!("Not a valid object name {hash}"))?;
+
+        Ok(Object {
+            kind,
+            expected_size: content.len() as u64,
+            reader: Box::new(std::io::Cursor::new(content)),
         })
     }
 }
@@ -145,7 +182,7 @@ impl<R> Object<R>
 where
     R: Read,
 {
-    /// Write the object to a writer and compute its SHA-1 hash.
+    /// Write the object to a writer and compute its object id under `hash_kind`.
     ///
     /// The object is written in git's object format:
     /// 1. Compressed with Zlib
@@ -153,14 +190,14 @@ where
     ///
     /// The hash is computed over the compressed data and returned.
     /// This can be used to compute the hash without writing to disk (e.g., with `std::io::sink()`).
-    pub(crate) fn write(mut self, writer: impl Write) -> anyhow::Result<[u8; 20]> {
+    pub(crate) fn write(mut self, writer: impl Write, hash_kind: HashKind) -> anyhow::Result<ObjectId> {
         // Create a Zlib encoder to compress the output
         let writer = ZlibEncoder::new(writer, Compression::default());
 
-        // Wrap the writer with a HashWriter to compute SHA-1 while writing
+        // Wrap the writer with a HashWriter to compute the configured hash while writing
         let mut writer = HashWriter {
             writer,
-            hasher: Sha1::new(),
+            hasher: Hasher::new(hash_kind),
         };
 
         // Write the header: "<kind> <size>\0"
@@ -171,28 +208,30 @@ where
 
         // Finish compression and get the final hash
         let _ = writer.writer.finish()?;
-        let hash = writer.hasher.finalize();
 
-        Ok(hash.into())
+        Ok(writer.hasher.finalize())
     }
 
-    /// Write the object to `.git/objects/` and return its hash.
+    /// Write the object to `.git/objects/` and return its object id.
     ///
     /// This function:
-    /// 1. Writes the object to a temporary file (computing the hash in the process)
+    /// 1. Writes the object to a temporary file (computing the `hash_kind` hash in the process)
     /// 2. Creates the appropriate subdirectory in `.git/objects/` (first 2 chars of hash)
     /// 3. Moves the temporary file to the final location: `.git/objects/ab/cdef...`
     ///
     /// The hash is used both to determine the file location and as the return value.
-    pub(crate) fn write_to_objects(self) -> anyhow::Result<[u8; 20]> {
+    pub(crate) fn write_to_objects(self, hash_kind: HashKind) -> anyhow::Result<ObjectId> {
         // Write to a temporary file first (this computes the hash)
         let tmp = "temporary";
-        let hash = self
-            .write(std::fs::File::create(tmp).context("construct temporary file for tree")?)
+        let object_id = self
+            .write(
+                std::fs::File::create(tmp).context("construct temporary file for tree")?,
+                hash_kind,
+            )
             .context("stream tree object into tree object file")?;
 
         // Encode hash as hex string for directory/filename construction
-        let hash_hex = hex::encode(hash);
+        let hash_hex = object_id.to_hex();
 
         // Create the subdirectory: .git/objects/ab/ (first 2 chars of hash)
         fs::create_dir_all(format!(".git/objects/{}/", &hash_hex[..2]))
@@ -205,17 +244,17 @@ where
         )
         .context("move tree file into .git/objects")?;
 
-        Ok(hash)
+        Ok(object_id)
     }
 }
 
-/// A writer that computes a SHA-1 hash of all data written to it.
+/// A writer that computes a hash of all data written to it.
 ///
-/// This wraps another writer and updates a SHA-1 hasher with every write.
-/// Used when writing git objects to compute their hash while writing.
+/// This wraps another writer and updates a [`Hasher`] with every write.
+/// Used when writing git objects to compute their id while writing.
 struct HashWriter<W> {
     writer: W,
-    hasher: Sha1,
+    hasher: Hasher,
 }
 
 impl<W> Write for HashWriter<W>
@@ -0,0 +1,329 @@
+use crate::objects::Kind;
+use anyhow::{anyhow, bail, Context};
+use flate2::bufread::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use sha1::{Digest, Sha1};
+use std::fs;
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+
+/// Parsed `.idx` (v2) file for a packfile.
+///
+/// Layout: a 4-byte magic (`\xfftOc`), a 4-byte version (must be 2), a 256-entry
+/// fanout table of big-endian u32 cumulative counts, the sorted 20-byte object
+/// names, a CRC32 per object, then 4-byte pack offsets (with the MSB set this is
+/// an index into a trailing table of 8-byte large offsets).
+struct PackIndex {
+    fanout: [u32; 256],
+    hashes: Vec<[u8; 20]>,
+    offsets: Vec<u64>,
+}
+
+const IDX_MAGIC: &[u8; 4] = b"\xfftOc";
+
+impl PackIndex {
+    fn open(path: &Path) -> anyhow::Result<Self> {
+        let data = fs::read(path).with_context(|| format!("read {}", path.display()))?;
+
+        anyhow::ensure!(data.len() >= 8 && &data[0..4] == IDX_MAGIC, "not a v2 .idx file: {}", path.display());
+        let version = u32::from_be_bytes(data[4..8].try_into().unwrap());
+        anyhow::ensure!(version == 2, "unsupported .idx version {version}");
+
+        let mut fanout = [0u32; 256];
+        let mut pos = 8;
+        for slot in fanout.iter_mut() {
+            *slot = u32::from_be_bytes(data[pos..pos + 4].try_into().unwrap());
+            pos += 4;
+        }
+        let count = fanout[255] as usize;
+
+        let mut hashes = Vec::with_capacity(count);
+        for _ in 0..count {
+            hashes.push(data[pos..pos + 20].try_into().unwrap());
+            pos += 20;
+        }
+
+        // CRC32 table: one u32 per object, not needed for lookups.
+        pos += count * 4;
+
+        let mut small_offsets = Vec::with_capacity(count);
+        for _ in 0..count {
+            small_offsets.push(u32::from_be_bytes(data[pos..pos + 4].try_into().unwrap()));
+            pos += 4;
+        }
+
+        let large_offsets_start = pos;
+        let mut offsets = Vec::with_capacity(count);
+        for &small in &small_offsets {
+            if small & 0x8000_0000 != 0 {
+                let idx = (small & 0x7fff_ffff) as usize;
+                let at = large_offsets_start + idx * 8;
+                offsets.push(u64::from_be_bytes(data[at..at + 8].try_into().unwrap()));
+            } else {
+                offsets.push(small as u64);
+            }
+        }
+
+        Ok(PackIndex { fanout, hashes, offsets })
+    }
+
+    /// Binary search the sorted hash table for `hash`, narrowed by the fanout table.
+    fn find_offset(&self, hash: &[u8; 20]) -> Option<u64> {
+        let lo = if hash[0] == 0 { 0 } else { self.fanout[hash[0] as usize - 1] as usize };
+        let hi = self.fanout[hash[0] as usize] as usize;
+        self.hashes[lo..hi]
+            .binary_search(hash)
+            .ok()
+            .map(|i| self.offsets[lo + i])
+    }
+}
+
+/// Try to resolve `hash` (as a 20-byte SHA1) against every packfile in
+/// `.git/objects/pack/`, returning its kind and fully-inflated content.
+pub(crate) fn resolve(hash: &[u8; 20]) -> anyhow::Result<Option<(Kind, Vec<u8>)>> {
+    let pack_dir = Path::new(".git/objects/pack");
+    let Ok(entries) = fs::read_dir(pack_dir) else {
+        return Ok(None);
+    };
+
+    for entry in entries {
+        let entry = entry?;
+        let idx_path = entry.path();
+        if idx_path.extension().and_then(|e| e.to_str()) != Some("idx") {
+            continue;
+        }
+        let index = PackIndex::open(&idx_path)?;
+        let Some(offset) = index.find_offset(hash) else {
+            continue;
+        };
+        let pack_path = idx_path.with_extension("pack");
+        let mut pack = Pack::open(&pack_path)?;
+        return Ok(Some(pack.read_at(offset)?));
+    }
+
+    Ok(None)
+}
+
+/// A packfile opened for random-access reads of individual entries.
+struct Pack {
+    data: Vec<u8>,
+    path: PathBuf,
+}
+
+impl Pack {
+    fn open(path: &Path) -> anyhow::Result<Self> {
+        let data = fs::read(path).with_context(|| format!("read {}", path.display()))?;
+        anyhow::ensure!(&data[0..4] == b"PACK", "not a packfile: {}", path.display());
+        Ok(Pack { data, path: path.to_path_buf() })
+    }
+
+    /// Read and fully reconstruct the object stored at `offset`, resolving any
+    /// chain of ofs-delta / ref-delta entries.
+    fn read_at(&mut self, offset: u64) -> anyhow::Result<(Kind, Vec<u8>)> {
+        let mut pos = offset as usize;
+
+        let first = self.data[pos];
+        let type_id = (first >> 4) & 0x7;
+        let mut size = (first & 0x0f) as u64;
+        let mut shift = 4;
+        let mut byte = first;
+        pos += 1;
+        while byte & 0x80 != 0 {
+            byte = self.data[pos];
+            pos += 1;
+            size |= ((byte & 0x7f) as u64) << shift;
+            shift += 7;
+        }
+
+        match type_id {
+            1..=4 => {
+                let kind = match type_id {
+                    1 => Kind::Commit,
+                    2 => Kind::Tree,
+                    3 => Kind::Blob,
+                    4 => bail!("tag objects are not supported"),
+                    _ => unreachable!(),
+                };
+                let content = self.inflate_at(pos, size as usize)?;
+                Ok((kind, content))
+            }
+            6 => {
+                // ofs-delta: a backward-relative base offset, then a delta stream.
+                let mut byte = self.data[pos];
+                pos += 1;
+                let mut base_rel = (byte & 0x7f) as u64;
+                while byte & 0x80 != 0 {
+                    byte = self.data[pos];
+                    pos += 1;
+                    base_rel = ((base_rel + 1) << 7) | (byte & 0x7f) as u64;
+                }
+                let base_offset = offset
+                    .checked_sub(base_rel)
+                    .ok_or_else(|| anyhow!("ofs-delta base offset underflow"))?;
+                let delta = self.inflate_at(pos, size as usize)?;
+                let (kind, base) = self.read_at(base_offset)?;
+                let content = apply_delta(&base, &delta)?;
+                Ok((kind, content))
+            }
+            7 => {
+                // ref-delta: a 20-byte absolute base hash, then a delta stream.
+                let base_hash: [u8; 20] = self.data[pos..pos + 20].try_into().unwrap();
+                pos += 20;
+                let delta = self.inflate_at(pos, size as usize)?;
+                let (kind, base) = self
+                    .read_by_hash(&base_hash)?
+                    .ok_or_else(|| anyhow!("ref-delta base {} not found", hex::encode(base_hash)))?;
+                let content = apply_delta(&base, &delta)?;
+                Ok((kind, content))
+            }
+            _ => bail!("unknown pack entry type {type_id}"),
+        }
+    }
+
+    fn read_by_hash(&mut self, hash: &[u8; 20]) -> anyhow::Result<Option<(Kind, Vec<u8>)>> {
+        let idx_path = self.path.with_extension("idx");
+        let index = PackIndex::open(&idx_path)?;
+        match index.find_offset(hash) {
+            Some(offset) => Ok(Some(self.read_at(offset)?)),
+            None => resolve(hash),
+        }
+    }
+
+    fn inflate_at(&self, pos: usize, expected_size: usize) -> anyhow::Result<Vec<u8>> {
+        let mut z = ZlibDecoder::new(&self.data[pos..]);
+        let mut out = Vec::with_capacity(expected_size);
+        z.read_to_end(&mut out).context("inflate pack entry")?;
+        anyhow::ensure!(
+            out.len() == expected_size,
+            "pack entry inflated to {} bytes, expected {expected_size}",
+            out.len()
+        );
+        Ok(out)
+    }
+}
+
+/// Apply a git delta stream (as produced for `ofs-delta`/`ref-delta` entries) to
+/// `base`, producing the reconstructed target content.
+fn apply_delta(base: &[u8], delta: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let mut pos = 0;
+    let source_size = read_delta_size(delta, &mut pos);
+    anyhow::ensure!(
+        source_size as usize == base.len(),
+        "delta source size {source_size} does not match base length {}",
+        base.len()
+    );
+    let target_size = read_delta_size(delta, &mut pos);
+
+    let mut target = Vec::with_capacity(target_size as usize);
+    while pos < delta.len() {
+        let opcode = delta[pos];
+        pos += 1;
+
+        if opcode & 0x80 != 0 {
+            let mut offset: u32 = 0;
+            for i in 0..4 {
+                if opcode & (1 << i) != 0 {
+                    offset |= (delta[pos] as u32) << (8 * i);
+                    pos += 1;
+                }
+            }
+            let mut size: u32 = 0;
+            for i in 0..3 {
+                if opcode & (1 << (4 + i)) != 0 {
+                    size |= (delta[pos] as u32) << (8 * i);
+                    pos += 1;
+                }
+            }
+            if size == 0 {
+                size = 0x10000;
+            }
+            let start = offset as usize;
+            let end = start + size as usize;
+            anyhow::ensure!(end <= base.len(), "delta copy op out of range");
+            target.extend_from_slice(&base[start..end]);
+        } else if opcode != 0 {
+            let end = pos + opcode as usize;
+            anyhow::ensure!(end <= delta.len(), "delta insert op out of range");
+            target.extend_from_slice(&delta[pos..end]);
+            pos = end;
+        } else {
+            bail!("invalid delta opcode 0");
+        }
+    }
+
+    anyhow::ensure!(
+        target.len() as u64 == target_size,
+        "delta produced {} bytes, expected {target_size}",
+        target.len()
+    );
+    Ok(target)
+}
+
+/// Read a delta stream size varint: 7 bits per byte, little-endian, bit 7 is the
+/// continuation flag. Used for the source- and target-size headers.
+fn read_delta_size(delta: &[u8], pos: &mut usize) -> u64 {
+    let mut result = 0u64;
+    let mut shift = 0;
+    loop {
+        let byte = delta[*pos];
+        *pos += 1;
+        result |= ((byte & 0x7f) as u64) << shift;
+        shift += 7;
+        if byte & 0x80 == 0 {
+            break;
+        }
+    }
+    result
+}
+
+/// Build a v2 packfile containing `objects`, each written as a non-delta
+/// entry (no delta compression - simple and correct, which is what the
+/// protocol server needs). Packs are always SHA-1 checksummed, matching
+/// [`PackIndex`]'s SHA-1-only `.idx` format.
+pub(crate) fn write_pack(objects: &[(Kind, Vec<u8>)]) -> anyhow::Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    buf.extend_from_slice(b"PACK");
+    buf.extend_from_slice(&2u32.to_be_bytes());
+    buf.extend_from_slice(&(objects.len() as u32).to_be_bytes());
+
+    for (kind, content) in objects {
+        write_pack_entry(&mut buf, *kind, content)?;
+    }
+
+    let checksum = Sha1::digest(&buf);
+    buf.extend_from_slice(&checksum);
+    Ok(buf)
+}
+
+/// Write one non-delta pack entry: the variable-length type/size header
+/// followed by the zlib-deflated content.
+fn write_pack_entry(buf: &mut Vec<u8>, kind: Kind, content: &[u8]) -> anyhow::Result<()> {
+    let type_id: u8 = match kind {
+        Kind::Commit => 1,
+        Kind::Tree => 2,
+        Kind::Blob => 3,
+    };
+
+    let mut size = content.len() as u64;
+    let mut first = (type_id << 4) | (size & 0x0f) as u8;
+    size >>= 4;
+    if size > 0 {
+        first |= 0x80;
+    }
+    buf.push(first);
+
+    while size > 0 {
+        let mut byte = (size & 0x7f) as u8;
+        size >>= 7;
+        if size > 0 {
+            byte |= 0x80;
+        }
+        buf.push(byte);
+    }
+
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(content).context("deflate pack entry")?;
+    buf.extend_from_slice(&encoder.finish().context("finish pack entry deflate")?);
+    Ok(())
+}
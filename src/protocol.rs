@@ -0,0 +1,204 @@
+use crate::commit::Commit;
+use crate::objects::{Kind, Object};
+use crate::{pack, refs, tree};
+use anyhow::Context;
+use std::collections::HashSet;
+use std::io::prelude::*;
+
+/// Serve protocol v2 over `input`/`output` (normally stdin/stdout), the way
+/// `git upload-pack` does for a `git clone`/`git fetch` peer: advertise
+/// capabilities, then handle `ls-refs` and `fetch` requests until the peer
+/// disconnects.
+pub fn execute(input: impl Read, output: impl Write) -> anyhow::Result<()> {
+    let mut input = std::io::BufReader::new(input);
+    let mut output = output;
+
+    advertise_capabilities(&mut output)?;
+
+    loop {
+        let Some(lines) = read_request(&mut input)? else {
+            return Ok(());
+        };
+        let Some(command_line) = lines.first() else {
+            continue;
+        };
+        let Some(command) = command_line.strip_prefix("command=") else {
+            anyhow::bail!("expected a command= line, got '{command_line}'");
+        };
+
+        match command {
+            "ls-refs" => ls_refs(&mut output)?,
+            "fetch" => fetch(&lines[1..], &mut output)?,
+            other => anyhow::bail!("unsupported command '{other}'"),
+        }
+    }
+}
+
+/// Write the initial protocol v2 greeting: the version line and the
+/// capabilities this server understands, terminated by a flush packet.
+fn advertise_capabilities(output: &mut impl Write) -> anyhow::Result<()> {
+    write_pkt_line(output, b"version 2\n")?;
+    write_pkt_line(output, b"ls-refs=ls-refs\n")?;
+    write_pkt_line(output, b"fetch=fetch\n")?;
+    write_flush(output)?;
+    Ok(())
+}
+
+/// Read one pkt-line-framed request: a command and its argument lines, up to
+/// the terminating flush packet. Delimiter packets (`0001`) separate sections
+/// within a single request (e.g. capabilities from `want`/`have` lines) and
+/// are not terminators, so reading continues past them. Returns `None` at EOF
+/// (the peer hung up).
+fn read_request(input: &mut impl BufRead) -> anyhow::Result<Option<Vec<String>>> {
+    let mut lines = Vec::new();
+    loop {
+        match read_pkt_line(input)? {
+            None => return Ok(if lines.is_empty() { None } else { Some(lines) }),
+            Some(PktLine::Flush) => return Ok(Some(lines)),
+            Some(PktLine::Delim) => continue,
+            Some(PktLine::Data(data)) => {
+                let line = String::from_utf8(data).context("pkt-line is not valid UTF-8")?;
+                lines.push(line.trim_end_matches('\n').to_string());
+            }
+        }
+    }
+}
+
+/// Handle `ls-refs`: list every ref (plus `HEAD`) this repository has.
+fn ls_refs(output: &mut impl Write) -> anyhow::Result<()> {
+    for (name, hash) in refs::list_all()? {
+        write_pkt_line(output, format!("{hash} {name}\n").as_bytes())?;
+    }
+    write_flush(output)?;
+    Ok(())
+}
+
+/// Handle `fetch`: collect `want`/`have` lines up to `done`, compute the
+/// objects reachable from the wants but not the haves, and stream them back
+/// as a packfile wrapped in `side-band-64k` channel-1 pkt-lines.
+fn fetch(args: &[String], output: &mut impl Write) -> anyhow::Result<()> {
+    let mut wants = Vec::new();
+    let mut haves = Vec::new();
+
+    for line in args {
+        if let Some(hash) = line.strip_prefix("want ") {
+            wants.push(hash.to_string());
+        } else if let Some(hash) = line.strip_prefix("have ") {
+            haves.push(hash.to_string());
+        }
+        // "done" and any other arguments (filters, shallow, ...) are ignored.
+    }
+
+    let wanted = reachable_from(&wants, false)?;
+    let common = reachable_from(&haves, true)?;
+
+    let mut objects = Vec::new();
+    for hash in wanted.difference(&common) {
+        let object = Object::read(hash).with_context(|| format!("read object {hash}"))?;
+        let kind = object.kind;
+        let mut content = Vec::new();
+        std::io::BufReader::new(object.reader)
+            .read_to_end(&mut content)
+            .with_context(|| format!("read content of {hash}"))?;
+        objects.push((kind, content));
+    }
+
+    let pack = pack::write_pack(&objects).context("build packfile")?;
+
+    write_pkt_line(output, b"packfile\n")?;
+    for chunk in pack.chunks(MAX_SIDEBAND_CHUNK) {
+        let mut band = Vec::with_capacity(chunk.len() + 1);
+        band.push(1); // side-band-64k channel 1: pack data
+        band.extend_from_slice(chunk);
+        write_pkt_line(output, &band)?;
+    }
+    write_flush(output)?;
+    Ok(())
+}
+
+/// Walk commits -> trees -> blobs from every hash in `starts`, returning the
+/// full set of reachable object hashes. When `best_effort` is set (used for
+/// the client's `have`s), an object this repository doesn't have is simply
+/// skipped rather than failing the whole walk.
+fn reachable_from(starts: &[String], best_effort: bool) -> anyhow::Result<HashSet<String>> {
+    let mut seen = HashSet::new();
+    let mut stack = starts.to_vec();
+
+    while let Some(hash) = stack.pop() {
+        if !seen.insert(hash.clone()) {
+            continue;
+        }
+
+        let object = match Object::read(&hash) {
+            Ok(object) => object,
+            Err(_) if best_effort => continue,
+            Err(e) => return Err(e).with_context(|| format!("read object {hash}")),
+        };
+
+        match object.kind {
+            Kind::Commit => {
+                let commit = Commit::parse(object.reader)
+                    .with_context(|| format!("parse commit {hash}"))?;
+                stack.push(commit.tree);
+                stack.extend(commit.parents);
+            }
+            Kind::Tree => {
+                for entry in tree::parse(object.reader).with_context(|| format!("parse tree {hash}"))? {
+                    stack.push(hex::encode(entry.hash));
+                }
+            }
+            Kind::Blob => {}
+        }
+    }
+
+    Ok(seen)
+}
+
+/// Max pkt-line payload is 65516 bytes; side-band packets spend one of those
+/// bytes on the channel indicator.
+const MAX_SIDEBAND_CHUNK: usize = 65516 - 1;
+
+enum PktLine {
+    Data(Vec<u8>),
+    Delim,
+    Flush,
+}
+
+/// Read one pkt-line: a 4-byte hex length (including itself), `0000` for a
+/// flush packet, `0001` for a delimiter, or that many bytes of data.
+/// Returns `None` on a clean EOF before any bytes are read.
+fn read_pkt_line(input: &mut impl BufRead) -> anyhow::Result<Option<PktLine>> {
+    if input.fill_buf().context("read pkt-line length")?.is_empty() {
+        return Ok(None);
+    }
+    let mut len_buf = [0u8; 4];
+    input.read_exact(&mut len_buf).context("read pkt-line length")?;
+
+    let len_str = std::str::from_utf8(&len_buf).context("pkt-line length isn't valid UTF-8")?;
+    let len = u32::from_str_radix(len_str, 16).context("invalid pkt-line length")? as usize;
+
+    match len {
+        0 => Ok(Some(PktLine::Flush)),
+        1 => Ok(Some(PktLine::Delim)),
+        n => {
+            let mut data = vec![0u8; n - 4];
+            input.read_exact(&mut data).context("read pkt-line data")?;
+            Ok(Some(PktLine::Data(data)))
+        }
+    }
+}
+
+/// Write one length-prefixed pkt-line: a 4-byte lowercase hex length
+/// (including the 4 header bytes) followed by `data`.
+fn write_pkt_line(output: &mut impl Write, data: &[u8]) -> anyhow::Result<()> {
+    anyhow::ensure!(data.len() + 4 <= 0xffff, "pkt-line payload too large");
+    write!(output, "{:04x}", data.len() + 4)?;
+    output.write_all(data)?;
+    Ok(())
+}
+
+/// Write a flush packet (`0000`), which ends a section in pkt-line framing.
+fn write_flush(output: &mut impl Write) -> anyhow::Result<()> {
+    output.write_all(b"0000")?;
+    Ok(())
+}
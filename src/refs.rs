@@ -0,0 +1,109 @@
+use anyhow::Context;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+/// Follow a `ref:` indirection chain (as stored in e.g. `.git/HEAD`) down to
+/// the hash it ultimately points at. `path` is relative to `.git/`. Falls
+/// back to `.git/packed-refs` when there's no loose ref file - `git gc`
+/// packs refs away, so a ref with no loose file is the common case, not the
+/// exception.
+pub(crate) fn resolve(path: &str) -> anyhow::Result<String> {
+    let contents = match fs::read_to_string(format!(".git/{path}")) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return packed_ref(path)?.with_context(|| format!("ref .git/{path} not found"));
+        }
+        Err(e) => return Err(e).with_context(|| format!("read .git/{path}")),
+    };
+    let contents = contents.trim();
+
+    match contents.strip_prefix("ref: ") {
+        Some(target) => resolve(target),
+        None => Ok(contents.to_string()),
+    }
+}
+
+/// Look `refname` up in `.git/packed-refs` (the `<hash> <refname>` lines;
+/// peeled-tag `^<hash>` lines are skipped).
+fn packed_ref(refname: &str) -> anyhow::Result<Option<String>> {
+    let Ok(contents) = fs::read_to_string(".git/packed-refs") else {
+        return Ok(None);
+    };
+
+    for line in contents.lines() {
+        if line.starts_with('#') || line.starts_with('^') {
+            continue;
+        }
+        if let Some((hash, name)) = line.split_once(' ') {
+            if name == refname {
+                return Ok(Some(hash.to_string()));
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+/// Resolve a commit-ish (`HEAD`, a branch name, or a raw hash) down to a commit hash.
+pub(crate) fn resolve_commit_ish(commit_ish: &str) -> anyhow::Result<String> {
+    if commit_ish.len() == 40 && commit_ish.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Ok(commit_ish.to_string());
+    }
+
+    let ref_path = if commit_ish == "HEAD" {
+        "HEAD".to_string()
+    } else {
+        format!("refs/heads/{commit_ish}")
+    };
+
+    resolve(&ref_path)
+}
+
+/// List every ref under `.git/refs` (recursively) plus `HEAD`, as
+/// `(name, hash)` pairs - the set a protocol v2 `ls-refs` response advertises.
+/// Also covers refs that only exist in `.git/packed-refs` (e.g. after `git
+/// gc`), since a loose file with the same name always wins.
+pub(crate) fn list_all() -> anyhow::Result<Vec<(String, String)>> {
+    let mut refs = vec![("HEAD".to_string(), resolve("HEAD")?)];
+    walk_refs(Path::new(".git/refs"), "refs", &mut refs)?;
+
+    let seen: HashSet<String> = refs.iter().map(|(name, _)| name.clone()).collect();
+    if let Ok(contents) = fs::read_to_string(".git/packed-refs") {
+        for line in contents.lines() {
+            if line.starts_with('#') || line.starts_with('^') {
+                continue;
+            }
+            if let Some((hash, name)) = line.split_once(' ') {
+                if !seen.contains(name) {
+                    refs.push((name.to_string(), hash.to_string()));
+                }
+            }
+        }
+    }
+
+    Ok(refs)
+}
+
+fn walk_refs(dir: &Path, prefix: &str, refs: &mut Vec<(String, String)>) -> anyhow::Result<()> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Ok(());
+    };
+
+    for entry in entries {
+        let entry = entry?;
+        let Some(name) = entry.file_name().to_str().map(str::to_string) else {
+            continue;
+        };
+        let path = entry.path();
+        let full_name = format!("{prefix}/{name}");
+
+        if path.is_dir() {
+            walk_refs(&path, &full_name, refs)?;
+        } else {
+            refs.push((full_name.clone(), resolve(&full_name)?));
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,77 @@
+use anyhow::Context;
+use std::fmt;
+use std::io::prelude::*;
+
+/// A single entry in a git tree object.
+pub(crate) struct TreeEntry {
+    pub(crate) mode: String,
+    pub(crate) name: String,
+    pub(crate) hash: [u8; 20],
+}
+
+/// What a tree entry's mode says it points at.
+pub(crate) enum EntryKind {
+    Tree,
+    Blob,
+}
+
+impl fmt::Display for EntryKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EntryKind::Tree => write!(f, "tree"),
+            EntryKind::Blob => write!(f, "blob"),
+        }
+    }
+}
+
+impl TreeEntry {
+    pub(crate) fn kind(&self) -> EntryKind {
+        match self.mode.as_str() {
+            "40000" => EntryKind::Tree,
+            _ => EntryKind::Blob,
+        }
+    }
+}
+
+/// Parse the decompressed content of a `Kind::Tree` object.
+///
+/// A tree's content is a flat sequence of entries with no separators:
+/// ASCII `<mode>` (no leading zero), a space, the raw file name bytes, a nul
+/// byte, then exactly 20 raw bytes of the child object's SHA1 - repeated
+/// until the reader is exhausted.
+pub(crate) fn parse(mut reader: impl Read) -> anyhow::Result<Vec<TreeEntry>> {
+    let mut entries = Vec::new();
+    let mut buf = Vec::new();
+
+    loop {
+        buf.clear();
+        let mut byte = [0u8; 1];
+        loop {
+            let n = reader.read(&mut byte).context("read tree entry")?;
+            if n == 0 {
+                anyhow::ensure!(buf.is_empty(), "tree entry truncated before nul byte");
+                return Ok(entries);
+            }
+            if byte[0] == 0 {
+                break;
+            }
+            buf.push(byte[0]);
+        }
+
+        let header = std::str::from_utf8(&buf).context("tree entry header isn't valid UTF-8")?;
+        let (mode, name) = header
+            .split_once(' ')
+            .context("tree entry header missing mode/name separator")?;
+
+        let mut hash = [0u8; 20];
+        reader
+            .read_exact(&mut hash)
+            .context("read tree entry hash")?;
+
+        entries.push(TreeEntry {
+            mode: mode.to_string(),
+            name: name.to_string(),
+            hash,
+        });
+    }
+}